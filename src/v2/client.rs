@@ -0,0 +1,70 @@
+use serde_json::Value;
+
+use crate::v2::collection::ChromaCollection;
+
+const DEFAULT_ENDPOINT: &str = "http://localhost:8000";
+
+/// Options for constructing a [`ChromaClient`].
+#[derive(Debug, Clone)]
+pub struct ChromaClientOptions {
+    pub url: String,
+}
+
+impl Default for ChromaClientOptions {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_ENDPOINT.to_string(),
+        }
+    }
+}
+
+/// An async client for a running Chroma server.
+pub struct ChromaClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl ChromaClient {
+    pub fn new(options: ChromaClientOptions) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: options.url,
+        }
+    }
+
+    /// Gets an existing collection by name, creating it with the given metadata if it doesn't
+    /// exist yet.
+    pub async fn get_or_create_collection(
+        &self,
+        name: String,
+        metadata: Option<Value>,
+    ) -> anyhow::Result<ChromaCollection> {
+        let res: Value = self
+            .client
+            .post(format!("{}/api/v1/collections", self.base_url))
+            .json(&serde_json::json!({
+                "name": name,
+                "metadata": metadata,
+                "get_or_create": true,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let id = res["id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("collection response missing id"))?
+            .to_string();
+        let dimension = res["dimension"].as_u64().map(|d| d as usize);
+
+        Ok(ChromaCollection {
+            id,
+            name,
+            client: self.client.clone(),
+            base_url: self.base_url.clone(),
+            dimension,
+        })
+    }
+}
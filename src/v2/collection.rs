@@ -0,0 +1,178 @@
+use serde_json::{json, Value};
+
+use crate::v2::commons::{Embedding, Metadata};
+use crate::v2::embeddings::EmbeddingFunction;
+
+/// The ids, documents, metadata and optionally pre-computed embeddings to add or upsert into a
+/// collection.
+///
+/// When `embeddings` is `None`, `documents` is embedded via the `embedding_function` passed to
+/// [`ChromaCollection::add`]/[`ChromaCollection::upsert`].
+#[derive(Debug, Clone, Default)]
+pub struct CollectionEntries {
+    pub ids: Vec<String>,
+    pub metadatas: Option<Vec<Metadata>>,
+    pub documents: Option<Vec<String>>,
+    pub embeddings: Option<Vec<Embedding>>,
+}
+
+/// A handle to a single collection on the Chroma server.
+pub struct ChromaCollection {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) client: reqwest::Client,
+    pub(crate) base_url: String,
+    /// The dimensionality this collection's index was created with, if the server reported one.
+    /// `None` for a brand new collection that hasn't received its first embedding yet.
+    pub(crate) dimension: Option<usize>,
+}
+
+impl ChromaCollection {
+    /// Errors early if `embedding` doesn't match this collection's known dimensionality.
+    fn validate_dimension(&self, embedding: &Embedding) -> anyhow::Result<()> {
+        if let Some(expected) = self.dimension {
+            if embedding.len() != expected {
+                anyhow::bail!(
+                    "embedding has {} dimensions, but collection \"{}\" expects {}",
+                    embedding.len(),
+                    self.name,
+                    expected
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds entries to the collection, embedding `documents` first with `embedding_function` if
+    /// `entries.embeddings` is `None`. Errors if `entries.embeddings` is `None` and either
+    /// `documents` or `embedding_function` is missing.
+    pub async fn add(
+        &self,
+        entries: CollectionEntries,
+        embedding_function: Option<Box<dyn EmbeddingFunction>>,
+    ) -> anyhow::Result<()> {
+        self.add_or_upsert("add", entries, embedding_function).await
+    }
+
+    /// Upserts entries into the collection, embedding `documents` first with `embedding_function`
+    /// if `entries.embeddings` is `None`. Errors if `entries.embeddings` is `None` and either
+    /// `documents` or `embedding_function` is missing.
+    pub async fn upsert(
+        &self,
+        entries: CollectionEntries,
+        embedding_function: Option<Box<dyn EmbeddingFunction>>,
+    ) -> anyhow::Result<()> {
+        self.add_or_upsert("upsert", entries, embedding_function).await
+    }
+
+    /// Queries the collection for the nearest neighbors of `query_texts`, embedding them first
+    /// with `embedding_function`. Errors if no `embedding_function` is provided.
+    pub async fn query(
+        &self,
+        query_texts: Vec<String>,
+        n_results: usize,
+        embedding_function: Option<Box<dyn EmbeddingFunction>>,
+    ) -> anyhow::Result<Value> {
+        let query_embeddings = match embedding_function {
+            Some(ef) => ef.embed(query_texts).await?,
+            None => anyhow::bail!("query_texts require an embedding_function to embed them"),
+        };
+
+        for embedding in &query_embeddings {
+            self.validate_dimension(embedding)?;
+        }
+
+        let res = self
+            .client
+            .post(format!(
+                "{}/api/v1/collections/{}/query",
+                self.base_url, self.id
+            ))
+            .json(&json!({
+                "query_embeddings": query_embeddings,
+                "n_results": n_results,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        Ok(res)
+    }
+
+    async fn add_or_upsert(
+        &self,
+        endpoint: &str,
+        mut entries: CollectionEntries,
+        embedding_function: Option<Box<dyn EmbeddingFunction>>,
+    ) -> anyhow::Result<()> {
+        if entries.embeddings.is_none() {
+            let docs = entries
+                .documents
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("entries must have either documents or embeddings"))?;
+            let ef = embedding_function
+                .ok_or_else(|| anyhow::anyhow!("documents require an embedding_function to embed them"))?;
+            entries.embeddings = Some(ef.embed(docs).await?);
+        }
+
+        for embedding in entries.embeddings.as_ref().unwrap() {
+            self.validate_dimension(embedding)?;
+        }
+
+        self.client
+            .post(format!(
+                "{}/api/v1/collections/{}/{}",
+                self.base_url, self.id, endpoint
+            ))
+            .json(&json!({
+                "ids": entries.ids,
+                "embeddings": entries.embeddings,
+                "metadatas": entries.metadatas,
+                "documents": entries.documents,
+            }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_collection(dimension: Option<usize>) -> ChromaCollection {
+        ChromaCollection {
+            id: "test-id".to_string(),
+            name: "test-collection".to_string(),
+            client: reqwest::Client::new(),
+            base_url: "http://localhost:8000".to_string(),
+            dimension,
+        }
+    }
+
+    #[test]
+    fn accepts_embeddings_matching_the_collection_dimension() {
+        let collection = test_collection(Some(3));
+
+        assert!(collection.validate_dimension(&vec![0.0, 0.0, 0.0]).is_ok());
+    }
+
+    #[test]
+    fn rejects_embeddings_that_dont_match_the_collection_dimension() {
+        let collection = test_collection(Some(3));
+
+        assert!(collection.validate_dimension(&vec![0.0, 0.0]).is_err());
+    }
+
+    #[test]
+    fn allows_any_dimension_when_the_collection_has_none_recorded() {
+        let collection = test_collection(None);
+
+        assert!(collection.validate_dimension(&vec![0.0]).is_ok());
+    }
+}
@@ -0,0 +1,9 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+/// A single embedding vector.
+pub type Embedding = Vec<f32>;
+
+/// Arbitrary key/value metadata attached to a document.
+pub type Metadata = HashMap<String, Value>;
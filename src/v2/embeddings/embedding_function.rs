@@ -0,0 +1,9 @@
+use async_trait::async_trait;
+
+use crate::v2::commons::Embedding;
+
+/// A source of embeddings for a set of documents.
+#[async_trait]
+pub trait EmbeddingFunction: Send + Sync {
+    async fn embed(&self, docs: Vec<String>) -> anyhow::Result<Vec<Embedding>>;
+}
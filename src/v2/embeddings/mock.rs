@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+
+use crate::v2::commons::Embedding;
+use crate::v2::embeddings::EmbeddingFunction;
+
+/// An `EmbeddingFunction` that returns a fixed-size vector of zeros for every document, without
+/// making any network calls. Useful for exercising collection plumbing in tests without a real
+/// embedding provider.
+pub struct MockEmbeddings {
+    dimensions: usize,
+}
+
+impl MockEmbeddings {
+    pub fn new(dimensions: usize) -> Self {
+        Self { dimensions }
+    }
+}
+
+#[async_trait]
+impl EmbeddingFunction for MockEmbeddings {
+    async fn embed(&self, docs: Vec<String>) -> anyhow::Result<Vec<Embedding>> {
+        Ok(docs.iter().map(|_| vec![0.0; self.dimensions]).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn returns_one_zero_vector_per_document() {
+        let embeddings = MockEmbeddings::new(3);
+        let docs = vec!["a".to_string(), "b".to_string()];
+
+        let result = embeddings.embed(docs).await.unwrap();
+
+        assert_eq!(result, vec![vec![0.0, 0.0, 0.0], vec![0.0, 0.0, 0.0]]);
+    }
+}
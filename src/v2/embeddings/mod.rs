@@ -0,0 +1,7 @@
+pub mod embedding_function;
+pub mod mock;
+pub mod ollama;
+pub mod openai;
+pub mod rest;
+
+pub use embedding_function::EmbeddingFunction;
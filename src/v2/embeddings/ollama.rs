@@ -0,0 +1,107 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::v2::commons::Embedding;
+use crate::v2::embeddings::EmbeddingFunction;
+
+const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+const OLLAMA_EMBEDDINGS_MODEL: &str = "nomic-embed-text";
+
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest {
+    pub model: String,
+    pub prompt: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    pub embedding: Vec<f32>,
+}
+
+/// Represents a local Ollama embeddings provider
+pub struct OllamaEmbeddings {
+    config: OllamaConfig,
+}
+
+/// Defaults to a local Ollama server on `http://localhost:11434` running the "nomic-embed-text"
+/// model.
+pub struct OllamaConfig {
+    pub base_url: String,
+    pub model: String,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            base_url: OLLAMA_BASE_URL.to_string(),
+            model: OLLAMA_EMBEDDINGS_MODEL.to_string(),
+        }
+    }
+}
+
+impl OllamaEmbeddings {
+    pub fn new(config: OllamaConfig) -> Self {
+        Self { config }
+    }
+
+    fn request_builder(&self, client: &reqwest::Client, prompt: String) -> reqwest::RequestBuilder {
+        client
+            .post(format!("{}/api/embeddings", self.config.base_url))
+            .json(&EmbeddingRequest {
+                model: self.config.model.clone(),
+                prompt,
+            })
+    }
+
+    async fn embed_one(&self, prompt: String) -> anyhow::Result<Embedding> {
+        let client = reqwest::Client::new();
+        let res = self
+            .request_builder(&client, prompt)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<EmbeddingResponse>()
+            .await?;
+
+        Ok(res.embedding)
+    }
+}
+
+#[async_trait]
+impl EmbeddingFunction for OllamaEmbeddings {
+    async fn embed(&self, docs: Vec<String>) -> anyhow::Result<Vec<Embedding>> {
+        let mut embeddings = Vec::with_capacity(docs.len());
+        for doc in docs {
+            embeddings.push(self.embed_one(doc).await?);
+        }
+
+        Ok(embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_points_at_a_local_ollama_server() {
+        let config = OllamaConfig::default();
+
+        assert_eq!(config.base_url, "http://localhost:11434");
+        assert_eq!(config.model, "nomic-embed-text");
+    }
+
+    #[test]
+    fn request_builder_targets_the_embeddings_endpoint() {
+        let embeddings = OllamaEmbeddings::new(OllamaConfig::default());
+        let client = reqwest::Client::new();
+
+        let req = embeddings
+            .request_builder(&client, "hello".to_string())
+            .build()
+            .unwrap();
+
+        assert_eq!(req.url().as_str(), "http://localhost:11434/api/embeddings");
+        assert_eq!(req.method(), reqwest::Method::POST);
+    }
+}
@@ -1,20 +1,83 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::header::RETRY_AFTER;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tiktoken_rs::CoreBPE;
 
 use crate::v2::commons::Embedding;
+use crate::v2::embeddings::EmbeddingFunction;
 
 const OPENAI_EMBEDDINGS_ENDPOINT: &str = "https://api.openai.com/v1/embeddings";
-const OPENAI_EMBEDDINGS_MODEL: &str = "text-embedding-3-small";
+const OPENAI_EMBEDDINGS_BATCH_SIZE: usize = 100;
+const OPENAI_MAX_RETRIES: u32 = 5;
+const OPENAI_BASE_RETRY_DELAY: Duration = Duration::from_millis(500);
+const OPENAI_MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// An embedding model exposed by OpenAI's embeddings endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingModel {
+    TextEmbeddingAda002,
+    TextEmbedding3Small,
+    TextEmbedding3Large,
+}
+
+impl EmbeddingModel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TextEmbeddingAda002 => "text-embedding-ada-002",
+            Self::TextEmbedding3Small => "text-embedding-3-small",
+            Self::TextEmbedding3Large => "text-embedding-3-large",
+        }
+    }
+
+    /// The maximum number of input tokens this model accepts per document.
+    pub fn max_token(&self) -> usize {
+        match self {
+            Self::TextEmbeddingAda002 => 8191,
+            Self::TextEmbedding3Small => 8191,
+            Self::TextEmbedding3Large => 8191,
+        }
+    }
+
+    /// The embedding length this model returns when no `dimensions` override is requested.
+    pub fn dimensions(&self) -> usize {
+        match self {
+            Self::TextEmbeddingAda002 => 1536,
+            Self::TextEmbedding3Small => 1536,
+            Self::TextEmbedding3Large => 3072,
+        }
+    }
+}
+
+impl Default for EmbeddingModel {
+    fn default() -> Self {
+        Self::TextEmbedding3Small
+    }
+}
+
+/// What to do with a document that encodes to more tokens than its model allows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverLimit {
+    /// Truncate to the model's token limit and re-decode the truncated tokens back to a string.
+    Truncate,
+    /// Return a descriptive error instead of sending the request.
+    Error,
+}
 
 #[derive(Debug, Deserialize)]
 struct EmbeddingData {
     pub embedding: Vec<f32>,
+    pub index: usize,
 }
 
 #[derive(Debug, Serialize)]
 struct EmbeddingRequest {
     pub model: String,
-    pub input: String,
+    pub input: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -24,7 +87,8 @@ struct EmbeddingResponse {
 
 /// Represents the OpenAI Embeddings provider
 pub struct OpenAIEmbeddings {
-    config: OpenAIConfig
+    config: OpenAIConfig,
+    tokenizer: CoreBPE,
 }
 
 /// Defaults to the "text-embedding-3-small" model
@@ -32,7 +96,21 @@ pub struct OpenAIEmbeddings {
 pub struct OpenAIConfig {
     pub api_endpoint: String,
     pub api_key: String,
-    pub model: String,
+    pub model: EmbeddingModel,
+    /// How many documents to send per embeddings request.
+    pub batch_size: usize,
+    /// What to do with a document that exceeds `model`'s token limit.
+    pub over_limit: OverLimit,
+    /// How many times to retry a request that failed with a `429` or `5xx` status.
+    pub max_retries: u32,
+    /// The delay before the first retry. Doubles on each subsequent attempt, capped at
+    /// `max_retry_delay`, unless the response carries a `Retry-After` header.
+    pub base_retry_delay: Duration,
+    pub max_retry_delay: Duration,
+    /// Requests a reduced embedding length from `model` (only supported by the v3 models). Must
+    /// not exceed `model.dimensions()`; checked in `new()`. A mismatch against an existing
+    /// collection's own dimensionality is caught separately, by `ChromaCollection` itself.
+    pub dimensions: Option<usize>,
 }
 
 impl Default for OpenAIConfig {
@@ -40,46 +118,154 @@ impl Default for OpenAIConfig {
         Self {
             api_endpoint: OPENAI_EMBEDDINGS_ENDPOINT.to_string(),
             api_key: std::env::var("OPENAI_API_KEY").expect("OPENAI_API_KEY env is not set"),
-            model: OPENAI_EMBEDDINGS_MODEL.to_string(),
+            model: EmbeddingModel::default(),
+            batch_size: OPENAI_EMBEDDINGS_BATCH_SIZE,
+            over_limit: OverLimit::Truncate,
+            max_retries: OPENAI_MAX_RETRIES,
+            base_retry_delay: OPENAI_BASE_RETRY_DELAY,
+            max_retry_delay: OPENAI_MAX_RETRY_DELAY,
+            dimensions: None,
         }
     }
 }
 
 impl OpenAIEmbeddings {
-    pub fn new(config: OpenAIConfig) -> Self {
-        Self { config }
+    pub fn new(config: OpenAIConfig) -> anyhow::Result<Self> {
+        if let Some(dimensions) = config.dimensions {
+            if dimensions > config.model.dimensions() {
+                anyhow::bail!(
+                    "requested {} dimensions, but \"{}\" supports at most {}",
+                    dimensions,
+                    config.model.name(),
+                    config.model.dimensions()
+                );
+            }
+        }
+
+        let tokenizer = tiktoken_rs::cl100k_base().expect("failed to load the cl100k_base tokenizer");
+        Ok(Self { config, tokenizer })
+    }
+
+    /// The embedding length this provider should return, accounting for `config.dimensions`. Used
+    /// to catch a malformed/unexpected OpenAI response; matching against a collection's own
+    /// dimensionality is validated separately by `ChromaCollection`.
+    fn expected_dimensions(&self) -> usize {
+        self.config.dimensions.unwrap_or_else(|| self.config.model.dimensions())
+    }
+
+    /// Encodes `doc` and, if it exceeds the configured model's token limit, either truncates it
+    /// back down to that limit or returns an error, depending on `self.config.over_limit`.
+    fn enforce_token_limit(&self, doc: String) -> anyhow::Result<String> {
+        let max_tokens = self.config.model.max_token();
+        let tokens = self.tokenizer.encode_with_special_tokens(&doc);
+        if tokens.len() <= max_tokens {
+            return Ok(doc);
+        }
+
+        match self.config.over_limit {
+            OverLimit::Truncate => self.decode_truncated(tokens[..max_tokens].to_vec()),
+            OverLimit::Error => Err(anyhow::anyhow!(
+                "document has {} tokens, which exceeds the {} token limit for model \"{}\"",
+                tokens.len(),
+                max_tokens,
+                self.config.model.name()
+            )),
+        }
+    }
+
+    /// Decodes `tokens` back to a string, dropping trailing tokens one at a time if the slice
+    /// ends mid-codepoint. cl100k_base token boundaries don't always line up with UTF-8 character
+    /// boundaries, so a naive decode of a truncated token slice can otherwise fail outright.
+    fn decode_truncated(&self, mut tokens: Vec<usize>) -> anyhow::Result<String> {
+        loop {
+            match self.tokenizer.decode(tokens.clone()) {
+                Ok(decoded) => return Ok(decoded),
+                Err(_) if !tokens.is_empty() => {
+                    tokens.pop();
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
 
     async fn post<T: Serialize>(&self, json_body: T) -> anyhow::Result<Value> {
         let client = reqwest::Client::new();
-        let res = client.post(&self.config.api_endpoint)
-            .body("the exact body that is sent")
-            .header("Content-Type", "application/json")
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
-            .json(&json_body)
-            .send()
-            .await?;
-
-        match res.error_for_status() {
-            Ok(res) => {
-                Ok(res.json().await?)
-            },
-            Err(e) => {
-                Err(e.into())
+        let mut attempt = 0;
+
+        loop {
+            let res = client
+                .post(&self.config.api_endpoint)
+                .header("Content-Type", "application/json")
+                .header("Authorization", format!("Bearer {}", self.config.api_key))
+                .json(&json_body)
+                .send()
+                .await?;
+
+            let status = res.status();
+            if status.is_success() {
+                return Ok(res.json().await?);
+            }
+
+            let retryable = status.as_u16() == 429 || status.is_server_error();
+            if !retryable || attempt >= self.config.max_retries {
+                return Err(res.error_for_status().unwrap_err().into());
             }
+
+            let delay = retry_after(&res).unwrap_or_else(|| self.backoff_delay(attempt));
+            tokio::time::sleep(delay).await;
+            attempt += 1;
         }
     }
 
-    pub async fn embed(&self, docs: Vec<String>) -> anyhow::Result<Vec<Embedding>> {
+    /// The exponential backoff delay for the given (zero-indexed) retry attempt, doubling the
+    /// configured base delay each attempt and capping at `max_retry_delay`.
+    fn backoff_delay(&self, attempt: u32) -> Duration {
+        let delay = self.config.base_retry_delay * 2u32.saturating_pow(attempt);
+        delay.min(self.config.max_retry_delay)
+    }
+}
+
+/// Reads the `Retry-After` header off a response, if present, as a number of seconds to wait.
+fn retry_after(res: &reqwest::Response) -> Option<Duration> {
+    res.headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[async_trait]
+impl EmbeddingFunction for OpenAIEmbeddings {
+    async fn embed(&self, docs: Vec<String>) -> anyhow::Result<Vec<Embedding>> {
+        let docs = docs
+            .into_iter()
+            .map(|doc| self.enforce_token_limit(doc))
+            .collect::<anyhow::Result<Vec<String>>>()?;
+
+        let expected_dimensions = self.expected_dimensions();
         let mut embeddings = Vec::new();
-        for doc in docs {
+        for chunk in docs.chunks(self.config.batch_size) {
             let req = EmbeddingRequest {
-                model: self.config.model.clone(),
-                input: doc,
+                model: self.config.model.name().to_string(),
+                input: chunk.to_vec(),
+                dimensions: self.config.dimensions,
             };
             let res = self.post(req).await?;
-            let body = serde_json::from_value::<EmbeddingResponse>(res)?;
-            embeddings.push(body.data[0].embedding.clone());
+            let mut body = serde_json::from_value::<EmbeddingResponse>(res)?;
+            body.data.sort_by_key(|data| data.index);
+
+            for data in body.data {
+                if data.embedding.len() != expected_dimensions {
+                    anyhow::bail!(
+                        "expected a {}-dimensional embedding but got {}",
+                        expected_dimensions,
+                        data.embedding.len()
+                    );
+                }
+                embeddings.push(data.embedding);
+            }
         }
 
         Ok(embeddings)
@@ -100,7 +286,7 @@ mod tests {
             .get_or_create_collection("open-ai-test-collection".to_string(), None)
             .await
             .unwrap();
-        let openai_embeddings = OpenAIEmbeddings::new(Default::default());
+        let openai_embeddings = OpenAIEmbeddings::new(Default::default()).unwrap();
 
         let docs = vec![
             "Once upon a time there was a frog".to_string(),
@@ -117,10 +303,109 @@ mod tests {
 
         collection
             .upsert(
-                collection_entries, 
-                Some(openai_embeddings),
+                collection_entries,
+                Some(Box::new(openai_embeddings)),
             )
             .await
             .unwrap();
     }
+
+    fn test_config(over_limit: OverLimit) -> OpenAIConfig {
+        OpenAIConfig {
+            api_endpoint: "http://localhost".to_string(),
+            api_key: "test-key".to_string(),
+            model: EmbeddingModel::TextEmbedding3Small,
+            batch_size: OPENAI_EMBEDDINGS_BATCH_SIZE,
+            over_limit,
+            max_retries: 0,
+            base_retry_delay: Duration::from_millis(1),
+            max_retry_delay: Duration::from_millis(1),
+            dimensions: None,
+        }
+    }
+
+    #[test]
+    fn truncates_non_ascii_documents_past_the_token_limit() {
+        let embeddings = OpenAIEmbeddings::new(test_config(OverLimit::Truncate)).unwrap();
+        let max_tokens = EmbeddingModel::TextEmbedding3Small.max_token();
+        let doc = "café ".repeat(max_tokens);
+
+        let truncated = embeddings.enforce_token_limit(doc).unwrap();
+
+        assert!(!truncated.is_empty());
+        let tokens = embeddings.tokenizer.encode_with_special_tokens(&truncated);
+        assert!(tokens.len() <= max_tokens);
+    }
+
+    #[test]
+    fn errors_on_documents_past_the_token_limit_when_configured_to() {
+        let embeddings = OpenAIEmbeddings::new(test_config(OverLimit::Error)).unwrap();
+        let doc = "word ".repeat(EmbeddingModel::TextEmbedding3Small.max_token());
+
+        assert!(embeddings.enforce_token_limit(doc).is_err());
+    }
+
+    #[test]
+    fn leaves_short_documents_untouched() {
+        let embeddings = OpenAIEmbeddings::new(test_config(OverLimit::Truncate)).unwrap();
+        let doc = "a short document".to_string();
+
+        assert_eq!(embeddings.enforce_token_limit(doc.clone()).unwrap(), doc);
+    }
+
+    #[test]
+    fn backoff_delay_doubles_and_caps() {
+        let mut config = test_config(OverLimit::Truncate);
+        config.base_retry_delay = Duration::from_millis(100);
+        config.max_retry_delay = Duration::from_millis(300);
+        let embeddings = OpenAIEmbeddings::new(config).unwrap();
+
+        assert_eq!(embeddings.backoff_delay(0), Duration::from_millis(100));
+        assert_eq!(embeddings.backoff_delay(1), Duration::from_millis(200));
+        assert_eq!(embeddings.backoff_delay(2), Duration::from_millis(300));
+    }
+
+    #[test]
+    fn retry_after_reads_the_header_as_seconds() {
+        let response: reqwest::Response = http::Response::builder()
+            .header(RETRY_AFTER, "7")
+            .body(reqwest::Body::from(Vec::new()))
+            .unwrap()
+            .into();
+
+        assert_eq!(retry_after(&response), Some(Duration::from_secs(7)));
+    }
+
+    #[test]
+    fn retry_after_is_none_without_the_header() {
+        let response: reqwest::Response = http::Response::builder()
+            .body(reqwest::Body::from(Vec::new()))
+            .unwrap()
+            .into();
+
+        assert_eq!(retry_after(&response), None);
+    }
+
+    #[test]
+    fn embedding_model_name_matches_the_api_model_id() {
+        assert_eq!(EmbeddingModel::TextEmbeddingAda002.name(), "text-embedding-ada-002");
+        assert_eq!(EmbeddingModel::TextEmbedding3Small.name(), "text-embedding-3-small");
+        assert_eq!(EmbeddingModel::TextEmbedding3Large.name(), "text-embedding-3-large");
+    }
+
+    #[test]
+    fn embedding_model_dimensions_differ_for_the_large_model() {
+        assert_eq!(EmbeddingModel::TextEmbeddingAda002.dimensions(), 1536);
+        assert_eq!(EmbeddingModel::TextEmbedding3Small.dimensions(), 1536);
+        assert_eq!(EmbeddingModel::TextEmbedding3Large.dimensions(), 3072);
+    }
+
+    #[test]
+    fn new_rejects_dimensions_above_the_model_maximum() {
+        let mut config = test_config(OverLimit::Truncate);
+        config.model = EmbeddingModel::TextEmbedding3Small;
+        config.dimensions = Some(EmbeddingModel::TextEmbedding3Small.dimensions() + 1);
+
+        assert!(OpenAIEmbeddings::new(config).is_err());
+    }
 }
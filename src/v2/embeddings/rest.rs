@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::v2::commons::Embedding;
+use crate::v2::embeddings::EmbeddingFunction;
+
+const INPUT_PLACEHOLDER: &str = "{{input}}";
+
+/// Configuration for a [`RestEmbeddings`] provider.
+///
+/// `request_template` is a JSON value containing the literal string `"{{input}}"` wherever the
+/// document text should be substituted. `response_path` is a dot-separated path (object keys or
+/// array indices) into the response body where the `Vec<f32>` embedding lives, e.g.
+/// `"data.0.embedding"`.
+pub struct RestConfig {
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub request_template: Value,
+    pub response_path: String,
+}
+
+/// An embedding provider backed by an arbitrary REST endpoint, described by a request template
+/// and a response path instead of a fixed schema.
+pub struct RestEmbeddings {
+    config: RestConfig,
+}
+
+impl RestEmbeddings {
+    pub fn new(config: RestConfig) -> Self {
+        Self { config }
+    }
+
+    fn build_request(&self, doc: &str) -> Value {
+        substitute(&self.config.request_template, doc)
+    }
+
+    fn request_builder(&self, client: &reqwest::Client, doc: &str) -> reqwest::RequestBuilder {
+        let mut req = client.post(&self.config.url).json(&self.build_request(doc));
+        for (key, value) in &self.config.headers {
+            req = req.header(key.as_str(), value.as_str());
+        }
+
+        req
+    }
+
+    fn extract_embedding(&self, body: &Value) -> anyhow::Result<Embedding> {
+        let mut current = body;
+        for segment in self.config.response_path.split('.') {
+            current = match segment.parse::<usize>() {
+                Ok(index) => current.get(index),
+                Err(_) => current.get(segment),
+            }
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "response_path segment \"{}\" not found in the response body",
+                    segment
+                )
+            })?;
+        }
+
+        serde_json::from_value::<Embedding>(current.clone()).map_err(|e| {
+            anyhow::anyhow!("response_path resolved to a non-numeric-array value: {e}")
+        })
+    }
+}
+
+/// Recursively replaces any `"{{input}}"` string in `template` with `doc`.
+fn substitute(template: &Value, doc: &str) -> Value {
+    match template {
+        Value::String(s) if s == INPUT_PLACEHOLDER => Value::String(doc.to_string()),
+        Value::Array(items) => Value::Array(items.iter().map(|v| substitute(v, doc)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), substitute(v, doc)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[async_trait]
+impl EmbeddingFunction for RestEmbeddings {
+    async fn embed(&self, docs: Vec<String>) -> anyhow::Result<Vec<Embedding>> {
+        let client = reqwest::Client::new();
+        let mut embeddings = Vec::with_capacity(docs.len());
+
+        for doc in docs {
+            let res = self
+                .request_builder(&client, &doc)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Value>()
+                .await?;
+            embeddings.push(self.extract_embedding(&res)?);
+        }
+
+        Ok(embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> RestConfig {
+        let mut headers = HashMap::new();
+        headers.insert("X-Api-Key".to_string(), "secret".to_string());
+
+        RestConfig {
+            url: "http://localhost:1234/embed".to_string(),
+            headers,
+            request_template: serde_json::json!({ "input": INPUT_PLACEHOLDER }),
+            response_path: "embedding".to_string(),
+        }
+    }
+
+    #[test]
+    fn custom_headers_are_attached_to_the_request() {
+        let embeddings = RestEmbeddings::new(test_config());
+        let client = reqwest::Client::new();
+
+        let req = embeddings
+            .request_builder(&client, "hello")
+            .build()
+            .unwrap();
+
+        assert_eq!(req.headers().get("X-Api-Key").unwrap(), "secret");
+    }
+
+    #[test]
+    fn substitutes_the_input_placeholder() {
+        let embeddings = RestEmbeddings::new(test_config());
+
+        assert_eq!(
+            embeddings.build_request("hello"),
+            serde_json::json!({ "input": "hello" })
+        );
+    }
+
+    #[test]
+    fn extracts_the_embedding_at_the_response_path() {
+        let embeddings = RestEmbeddings::new(test_config());
+        let body = serde_json::json!({ "embedding": [0.1, 0.2, 0.3] });
+
+        assert_eq!(embeddings.extract_embedding(&body).unwrap(), vec![0.1, 0.2, 0.3]);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod client;
+pub mod collection;
+pub mod commons;
+pub mod embeddings;
+
+pub use client::{ChromaClient, ChromaClientOptions};